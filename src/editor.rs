@@ -0,0 +1,58 @@
+//! The board editor: authoring starting boards (obstacle tiles) by hand instead of only
+//! watching random play.
+
+use std::collections::{HashSet, VecDeque};
+
+use hex2d::Coordinate;
+
+use crate::game::GRID_RADIUS;
+
+/// The active editing tool in the board editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CurrentTool {
+    /// Pans/zooms the camera as usual; no editing.
+    #[default]
+    Move,
+    /// Toggles the hovered tile on click or drag.
+    Brush,
+    /// Flood-fills from the clicked tile, flipping every connected same-valued tile.
+    Fill,
+    /// Fills the hex range between two clicked corners.
+    Rectangle,
+}
+
+/// Returns the set of tiles reachable from `origin_tile` by repeatedly stepping to
+/// in-bounds neighbors that have the same obstacle status as `origin_tile` itself
+/// (a flood fill, implemented as a BFS over `Coordinate::neighbors()`).
+pub fn flood_fill(board: &HashSet<Coordinate<i32>>, origin_tile: Coordinate<i32>) -> HashSet<Coordinate<i32>> {
+    let origin = Coordinate::new(0, 0);
+    let target_value = board.contains(&origin_tile);
+
+    let mut visited = HashSet::new();
+    visited.insert(origin_tile);
+    let mut queue = VecDeque::new();
+    queue.push_back(origin_tile);
+
+    while let Some(tile) = queue.pop_front() {
+        for neighbor in tile.neighbors() {
+            if neighbor.distance(origin) <= GRID_RADIUS as i32
+                && board.contains(&neighbor) == target_value
+                && visited.insert(neighbor)
+            {
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    visited
+}
+
+/// Returns the in-bounds tiles in the axial-coordinate box spanning `a` and `b`.
+pub fn rect_tiles(a: Coordinate<i32>, b: Coordinate<i32>) -> Vec<Coordinate<i32>> {
+    let origin = Coordinate::new(0, 0);
+    let (min_x, max_x) = (a.x.min(b.x), a.x.max(b.x));
+    let (min_y, max_y) = (a.y.min(b.y), a.y.max(b.y));
+    (min_x..=max_x)
+        .flat_map(|x| (min_y..=max_y).map(move |y| Coordinate::new(x, y)))
+        .filter(|tile| tile.distance(origin) <= GRID_RADIUS as i32)
+        .collect()
+}
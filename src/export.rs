@@ -0,0 +1,172 @@
+//! Rendering matches to an offscreen buffer and exporting them as animated GIFs.
+
+use std::io::{self, Write};
+
+use eframe::egui::{Color32, Pos2, Stroke};
+use image::{Rgba, RgbaImage};
+use imageproc::drawing::{draw_filled_circle_mut, draw_line_segment_mut, draw_polygon_mut, Blend};
+use imageproc::point::Point;
+
+use crate::game::GameState;
+use crate::render::{self, Canvas};
+use crate::util::transforms::Transform;
+use crate::{GRID_HEIGHT, GRID_WIDTH, PLAYER_COLORS};
+
+/// The fixed color palette used for GIF quantization: the board's background grays plus
+/// each player's color.
+fn palette() -> Vec<[u8; 3]> {
+    let mut colors = vec![[10, 10, 10], [50, 50, 50], [255, 255, 255]];
+    colors.extend(PLAYER_COLORS.map(|c| [c.r(), c.g(), c.b()]));
+    colors
+}
+
+/// Finds the palette index whose color is closest to `color`.
+fn nearest_palette_index(palette: &[[u8; 3]], color: Rgba<u8>) -> u8 {
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|&(_, &[r, g, b])| {
+            let dr = r as i32 - color.0[0] as i32;
+            let dg = g as i32 - color.0[1] as i32;
+            let db = b as i32 - color.0[2] as i32;
+            dr * dr + dg * dg + db * db
+        })
+        .expect("palette is non-empty")
+        .0 as u8
+}
+
+fn to_rgba(color: Color32) -> Rgba<u8> {
+    Rgba([color.r(), color.g(), color.b(), color.a()])
+}
+
+fn to_point(p: Pos2) -> Point<i32> {
+    Point::new(p.x.round() as i32, p.y.round() as i32)
+}
+
+/// A [`Canvas`] that rasterizes directly into an offscreen RGBA buffer, for GIF export.
+///
+/// Draws through `imageproc`'s [`Blend`] wrapper rather than straight onto the buffer, since
+/// `render_frame` relies on translucent fills and strokes to match the live egui view -- drawn
+/// without blending, those would overwrite existing pixels outright instead of tinting them.
+struct BufferCanvas<'a> {
+    buffer: Blend<&'a mut RgbaImage>,
+}
+
+impl Canvas for BufferCanvas<'_> {
+    fn fill_background(&mut self, color: Color32) {
+        let rgba = to_rgba(color);
+        for pixel in self.buffer.0.pixels_mut() {
+            *pixel = rgba;
+        }
+    }
+
+    fn polygon(&mut self, points: &[Pos2], fill: Color32, stroke: Stroke) {
+        if fill.a() > 0 {
+            // `points` is closed (first corner repeated as the last), which is how egui's
+            // `Shape::convex_polygon` wants it; `draw_polygon_mut` closes the polygon itself
+            // and panics if given an already-closed list, so drop the repeated corner.
+            let poly_points: Vec<Point<i32>> =
+                points[..points.len() - 1].iter().copied().map(to_point).collect();
+            draw_polygon_mut(&mut self.buffer, &poly_points, to_rgba(fill));
+        }
+        if stroke.width > 0.0 {
+            for edge in points.windows(2) {
+                draw_line_segment_mut(
+                    &mut self.buffer,
+                    (edge[0].x, edge[0].y),
+                    (edge[1].x, edge[1].y),
+                    to_rgba(stroke.color),
+                );
+            }
+        }
+    }
+
+    fn line(&mut self, points: [Pos2; 2], stroke: Stroke) {
+        draw_line_segment_mut(
+            &mut self.buffer,
+            (points[0].x, points[0].y),
+            (points[1].x, points[1].y),
+            to_rgba(stroke.color),
+        );
+    }
+
+    fn quadratic_bezier(&mut self, points: [Pos2; 3], stroke: Stroke) {
+        // Flatten the curve into line segments; a fixed step count is plenty for GIF output.
+        const STEPS: usize = 16;
+        let color = to_rgba(stroke.color);
+        let point_at = |t: f32| {
+            let mt = 1.0 - t;
+            let v = points[0].to_vec2() * (mt * mt)
+                + points[1].to_vec2() * (2.0 * mt * t)
+                + points[2].to_vec2() * (t * t);
+            Pos2::new(v.x, v.y)
+        };
+        let mut prev = points[0];
+        for step in 1..=STEPS {
+            let next = point_at(step as f32 / STEPS as f32);
+            draw_line_segment_mut(&mut self.buffer, (prev.x, prev.y), (next.x, next.y), color);
+            prev = next;
+        }
+    }
+
+    fn circle(&mut self, center: Pos2, radius: f32, fill: Color32) {
+        draw_filled_circle_mut(
+            &mut self.buffer,
+            (center.x.round() as i32, center.y.round() as i32),
+            radius.round() as i32,
+            to_rgba(fill),
+        );
+    }
+}
+
+/// Renders `frames` to offscreen `width`x`height` RGBA buffers (reusing the same board/path
+/// drawing as the live viewer) and encodes them as an animated GIF, `delay_ms` apart.
+pub fn export_gif(
+    frames: &[GameState],
+    width: u16,
+    height: u16,
+    delay_ms: u16,
+    draw_lines: bool,
+    highlight_edges: bool,
+    writer: impl Write,
+) -> io::Result<()> {
+    let palette = palette();
+    let flat_palette: Vec<u8> = palette.iter().flatten().copied().collect();
+
+    let mut encoder = gif::Encoder::new(writer, width, height, &flat_palette)
+        .map_err(io::Error::other)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+
+    let transform = Transform::new_letterboxed(
+        Pos2::new(-GRID_WIDTH / 2.0, GRID_HEIGHT / 2.0),
+        Pos2::new(GRID_WIDTH / 2.0, -GRID_HEIGHT / 2.0),
+        Pos2::new(0.0, 0.0),
+        Pos2::new(width as f32, height as f32),
+    );
+
+    for frame_index in 0..frames.len() {
+        let mut buffer = RgbaImage::new(width as u32, height as u32);
+        {
+            let mut canvas = BufferCanvas {
+                buffer: Blend(&mut buffer),
+            };
+            render::render_frame(
+                frames,
+                frame_index,
+                &transform,
+                &mut canvas,
+                draw_lines,
+                highlight_edges,
+            );
+        }
+
+        let indices: Vec<u8> = buffer
+            .pixels()
+            .map(|&p| nearest_palette_index(&palette, p))
+            .collect();
+        let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, indices, None);
+        gif_frame.delay = delay_ms / 10; // The GIF format's delay unit is hundredths of a second.
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
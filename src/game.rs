@@ -1,7 +1,319 @@
+use std::collections::HashSet;
+
+use hex2d::{Coordinate, Direction, Spin};
+use serde::{Deserialize, Serialize};
+
 pub type PlayerID = nonmax::NonMaxU8;
 
 /// The number of tiles from the center tile to a tile on the edge of the board
 /// (not including the center tile itself).
 pub const GRID_RADIUS: u32 = 40;
 
-pub struct GameState {}
+/// A hex board edge, identified by its two endpoint tiles in a canonical (sorted) order.
+type Edge = (Coordinate<i32>, Coordinate<i32>);
+
+/// Returns the canonical (sorted) representation of the edge between two adjacent tiles.
+fn edge_between(a: Coordinate<i32>, b: Coordinate<i32>) -> Edge {
+    (a.min(b), a.max(b))
+}
+
+/// A small, fast, seedable pseudorandom number generator (xorshift32).
+///
+/// Matches are driven by this instead of `rand::thread_rng()` so that a whole game is
+/// fully reproducible from its seed alone -- essential for a deterministic bot arena.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct XorShift(u32);
+
+impl XorShift {
+    /// Creates a new RNG from the given seed. A seed of `0` is remapped, since xorshift
+    /// gets stuck at `0` forever.
+    pub fn new(seed: u32) -> Self {
+        Self(if seed == 0 { 0x9E37_79B9 } else { seed })
+    }
+
+    /// Returns the next pseudorandom `u32` and advances the generator's state.
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    /// Returns a pseudorandom index in `0..len`. Panics if `len == 0`.
+    pub fn gen_range(&mut self, len: usize) -> usize {
+        assert_ne!(len, 0);
+        (self.next_u32() as usize) % len
+    }
+}
+
+/// The state of a single player within a `GameState`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub id: PlayerID,
+    /// The tile the player currently occupies.
+    pub tile: Coordinate<i32>,
+    /// The edge the player most recently crossed to reach `tile`, if any.
+    pub last_edge: Option<Edge>,
+    pub alive: bool,
+}
+
+/// The full state of a match at a point in time.
+///
+/// Players extend a path along hexagon edges; an edge may be claimed by only one player,
+/// 180-degree reversals are illegal, and a player with no legal move is eliminated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameState {
+    rng: XorShift,
+    pub players: Vec<PlayerState>,
+    pub occupied_edges: HashSet<Edge>,
+    /// Tiles that are blocked off (e.g. by the board editor) and cannot be entered.
+    pub obstacles: HashSet<Coordinate<i32>>,
+}
+
+impl GameState {
+    /// Creates a new game with `num_players` players spread evenly around a starting ring,
+    /// driven by the given RNG seed, and no obstacles.
+    pub fn new(seed: u32, num_players: u8) -> Self {
+        Self::new_with_obstacles(seed, num_players, HashSet::new())
+    }
+
+    /// Like `new`, but starting from a board that already has some tiles blocked off.
+    /// Players are still placed on the starting ring: each takes its evenly spaced spot if
+    /// free, otherwise the next free tile after it in clockwise ring order.
+    pub fn new_with_obstacles(
+        seed: u32,
+        num_players: u8,
+        obstacles: HashSet<Coordinate<i32>>,
+    ) -> Self {
+        let origin = Coordinate::new(0, 0);
+        let start_ring: Vec<Coordinate<i32>> = origin
+            .ring_iter((GRID_RADIUS / 4) as i32, Spin::CW(Direction::XY))
+            .collect();
+        let players = (0..num_players)
+            .map(|i| {
+                let preferred = i as usize * start_ring.len() / num_players as usize;
+                let tile = (0..start_ring.len())
+                    .map(|offset| start_ring[(preferred + offset) % start_ring.len()])
+                    .find(|tile| !obstacles.contains(tile))
+                    .expect("the entire starting ring is blocked by obstacles");
+                PlayerState {
+                    id: PlayerID::new(i).expect("too many players"),
+                    tile,
+                    last_edge: None,
+                    alive: true,
+                }
+            })
+            .collect();
+        Self {
+            rng: XorShift::new(seed),
+            players,
+            occupied_edges: HashSet::new(),
+            obstacles,
+        }
+    }
+
+    /// Returns the tiles `player` may legally move to this turn (empty if eliminated or stuck),
+    /// given `occupied_edges` as the set of edges already claimed.
+    ///
+    /// Takes `occupied_edges` explicitly (rather than always using `self.occupied_edges`) so
+    /// that `step`/`step_random` can account for edges claimed earlier in the *same* turn by
+    /// other players, not just edges claimed in prior turns.
+    fn legal_moves_given(
+        &self,
+        player: &PlayerState,
+        occupied_edges: &HashSet<Edge>,
+    ) -> Vec<Coordinate<i32>> {
+        if !player.alive {
+            return Vec::new();
+        }
+        let origin = Coordinate::new(0, 0);
+        player
+            .tile
+            .neighbors()
+            .into_iter()
+            .filter(|&neighbor| {
+                neighbor.distance(origin) <= GRID_RADIUS as i32
+                    && !self.obstacles.contains(&neighbor)
+                    && player.last_edge != Some(edge_between(player.tile, neighbor))
+                    && !occupied_edges.contains(&edge_between(player.tile, neighbor))
+            })
+            .collect()
+    }
+
+    /// Returns the tiles `player` may legally move to this turn (empty if eliminated or stuck).
+    pub fn legal_moves(&self, player: &PlayerState) -> Vec<Coordinate<i32>> {
+        self.legal_moves_given(player, &self.occupied_edges)
+    }
+
+    /// Advances the game by one turn, given each player's chosen move (`None` for a player
+    /// that is already eliminated, or has no legal move and is being eliminated this turn).
+    ///
+    /// Players are processed in order, and each one's move is checked against the edges
+    /// claimed so far *this turn* as well as prior turns, so two players can't both cross the
+    /// same previously-unclaimed edge in a single `step` call.
+    ///
+    /// Panics if a provided move is not in that player's `legal_moves()` once earlier players'
+    /// moves this turn are taken into account.
+    pub fn step(&self, moves: &[Option<Coordinate<i32>>]) -> Self {
+        assert_eq!(moves.len(), self.players.len());
+        let mut occupied_edges = self.occupied_edges.clone();
+        let mut players = self.players.clone();
+        for (player, &chosen_move) in players.iter_mut().zip(moves) {
+            if !player.alive {
+                continue;
+            }
+            match chosen_move {
+                Some(next_tile) => {
+                    assert!(
+                        self.legal_moves_given(player, &occupied_edges).contains(&next_tile),
+                        "illegal move for player {:?}: {next_tile:?}",
+                        player.id,
+                    );
+                    let edge = edge_between(player.tile, next_tile);
+                    occupied_edges.insert(edge);
+                    player.last_edge = Some(edge);
+                    player.tile = next_tile;
+                }
+                None => player.alive = false,
+            }
+        }
+        Self {
+            rng: self.rng,
+            players,
+            occupied_edges,
+            obstacles: self.obstacles.clone(),
+        }
+    }
+
+    /// Like `step`, but also advances the RNG exactly as `step_random` would have while
+    /// choosing these moves -- one `gen_range` draw per player with a nonempty legal-moves
+    /// set, in player order. Used to replay a recorded `step_random` session (e.g. from a
+    /// saved [`crate::replay::Replay`]) so the RNG ends up exactly where an uninterrupted
+    /// session would have left it, rather than back at the seed's untouched initial state.
+    pub fn step_replay(&self, moves: &[Option<Coordinate<i32>>]) -> Self {
+        let mut rng = self.rng;
+        let mut occupied_edges = self.occupied_edges.clone();
+        for (player, &chosen_move) in self.players.iter().zip(moves) {
+            let legal = self.legal_moves_given(player, &occupied_edges);
+            if !legal.is_empty() {
+                rng.gen_range(legal.len());
+            }
+            if let Some(next_tile) = chosen_move {
+                occupied_edges.insert(edge_between(player.tile, next_tile));
+            }
+        }
+        let mut next = self.step(moves);
+        next.rng = rng;
+        next
+    }
+
+    /// Advances the game by one turn, eliminating any player with no legal move and
+    /// otherwise choosing a uniformly random legal move for each player. Returns the new
+    /// state along with the moves that were chosen, so a match can be recorded as a replay.
+    pub fn step_random(&self) -> (Self, Vec<Option<Coordinate<i32>>>) {
+        let mut rng = self.rng;
+        let mut occupied_edges = self.occupied_edges.clone();
+        let moves: Vec<Option<Coordinate<i32>>> = self
+            .players
+            .iter()
+            .map(|player| {
+                let legal = self.legal_moves_given(player, &occupied_edges);
+                if legal.is_empty() {
+                    None
+                } else {
+                    let chosen = legal[rng.gen_range(legal.len())];
+                    occupied_edges.insert(edge_between(player.tile, chosen));
+                    Some(chosen)
+                }
+            })
+            .collect();
+        let mut next = self.step(&moves);
+        next.rng = rng;
+        (next, moves)
+    }
+
+    /// Returns the winning player, if exactly one player remains alive.
+    pub fn winner(&self) -> Option<PlayerID> {
+        let mut alive = self.players.iter().filter(|p| p.alive);
+        let winner = alive.next()?;
+        alive.next().is_none().then_some(winner.id)
+    }
+
+    /// Returns `true` if the match has concluded: either a winner has emerged, or every
+    /// remaining player (including the simultaneous-elimination case of zero survivors) has
+    /// no legal move left to make.
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+            || self
+                .players
+                .iter()
+                .filter(|p| p.alive)
+                .all(|p| self.legal_moves(p).is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_over_true_for_single_player() {
+        assert!(GameState::new(1, 1).is_over());
+    }
+
+    #[test]
+    fn is_over_false_at_start_with_multiple_players() {
+        assert!(!GameState::new(1, 2).is_over());
+    }
+
+    #[test]
+    fn legal_moves_excludes_obstacles_and_last_edge() {
+        let mut game = GameState::new(1, 2);
+        let start = game.players[0].tile;
+        let neighbors = start.neighbors();
+        let open = neighbors[0];
+        game.obstacles = neighbors[1..].iter().copied().collect();
+
+        assert_eq!(game.legal_moves(&game.players[0]), vec![open]);
+
+        let after = game.step(&[Some(open), None]);
+        assert_eq!(after.players[0].tile, open);
+        assert!(!after.legal_moves(&after.players[0]).contains(&start));
+    }
+
+    #[test]
+    #[should_panic(expected = "illegal move")]
+    fn step_rejects_same_turn_edge_collision() {
+        let mut game = GameState::new(1, 2);
+        let a = game.players[0].tile;
+        let b = a.neighbors()[0];
+        game.players[1].tile = b;
+
+        // Both players cross the same never-before-used edge (a, b) from opposite ends.
+        game.step(&[Some(b), Some(a)]);
+    }
+
+    #[test]
+    fn is_over_when_all_players_are_simultaneously_trapped() {
+        let mut game = GameState::new(1, 2);
+        let neighbors0 = game.players[0].tile.neighbors();
+        let neighbors1 = game.players[1].tile.neighbors();
+        game.obstacles = neighbors0.into_iter().chain(neighbors1).collect();
+
+        assert!(game.legal_moves(&game.players[0]).is_empty());
+        assert!(game.legal_moves(&game.players[1]).is_empty());
+        assert!(game.winner().is_none());
+        assert!(game.is_over());
+    }
+
+    #[test]
+    #[should_panic(expected = "blocked")]
+    fn new_with_obstacles_panics_if_the_starting_ring_is_fully_blocked() {
+        let origin = Coordinate::new(0, 0);
+        let obstacles: HashSet<_> =
+            origin.ring_iter((GRID_RADIUS / 4) as i32, Spin::CW(Direction::XY)).collect();
+        GameState::new_with_obstacles(1, 1, obstacles);
+    }
+}
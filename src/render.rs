@@ -0,0 +1,96 @@
+//! Frame rendering shared between the live egui viewer and the offscreen GIF exporter.
+//!
+//! Both consumers draw the same board and player paths; they only differ in *how* a
+//! polygon, line, curve, or circle ends up on screen (or in a pixel buffer), so that part
+//! is abstracted behind [`Canvas`].
+
+use eframe::egui::{Color32, Pos2, Stroke};
+use hex2d::{Coordinate, Direction, Spin};
+
+use crate::game::{GameState, GRID_RADIUS};
+use crate::util::transforms::Transform;
+use crate::{edge_corners, tile_center, HEXAGON_CORNERS, PLAYER_COLORS};
+
+/// A drawing surface that [`render_frame`] targets. Implemented once for the live egui
+/// painter and once for an offscreen RGBA buffer.
+pub trait Canvas {
+    fn fill_background(&mut self, color: Color32);
+    fn polygon(&mut self, points: &[Pos2], fill: Color32, stroke: Stroke);
+    fn line(&mut self, points: [Pos2; 2], stroke: Stroke);
+    fn quadratic_bezier(&mut self, points: [Pos2; 3], stroke: Stroke);
+    fn circle(&mut self, center: Pos2, radius: f32, fill: Color32);
+}
+
+/// Draws the board and every player's path (accumulated through `frames[..=frame_index]`)
+/// onto `canvas`, mapping world coordinates to `canvas` space with `transform`.
+pub fn render_frame(
+    frames: &[GameState],
+    frame_index: usize,
+    transform: &Transform,
+    canvas: &mut impl Canvas,
+    draw_lines: bool,
+    highlight_edges: bool,
+) {
+    canvas.fill_background(Color32::from_gray(10));
+
+    let game = &frames[frame_index];
+
+    let origin = Coordinate::new(0, 0);
+    for r in 0..=GRID_RADIUS {
+        for tile in origin.ring_iter(r as i32, Spin::CW(Direction::XY)) {
+            let center = tile_center(tile);
+            let corners = HEXAGON_CORNERS.map(|p| transform.map_point(center + p));
+            let fill = if game.obstacles.contains(&tile) {
+                Color32::from_gray(30)
+            } else if r == 0 {
+                Color32::from_rgba_unmultiplied(255, 128, 0, 15)
+            } else if r == GRID_RADIUS {
+                Color32::WHITE.gamma_multiply(0.05)
+            } else {
+                Color32::TRANSPARENT
+            };
+            canvas.polygon(&corners, fill, Stroke::new(0.5, Color32::from_gray(50)));
+        }
+    }
+    for (player_index, &color) in PLAYER_COLORS.iter().enumerate().take(game.players.len()) {
+        let mut last_edge_screen = None;
+        for i in 1..=frame_index {
+            let prev_tile = frames[i - 1].players[player_index].tile;
+            let tile = frames[i].players[player_index].tile;
+            if tile == prev_tile {
+                // The player didn't move this turn (e.g. it was already eliminated).
+                continue;
+            }
+
+            let [e1, e2] = edge_corners(tile, prev_tile).map(|p| transform.map_point(p));
+            let edge_screen = e1 + (e2 - e1) / 2.0;
+
+            if highlight_edges {
+                // Redraw the hexagon edge to show that it is now off-limits.
+                canvas.line([e1, e2], Stroke::new(1.0, Color32::WHITE));
+            }
+
+            if draw_lines {
+                // Draw the curved segment of the player's line.
+                if let Some(last_edge_screen) = last_edge_screen {
+                    canvas.quadratic_bezier(
+                        [
+                            last_edge_screen,
+                            transform.map_point(tile_center(prev_tile)),
+                            edge_screen,
+                        ],
+                        Stroke::new(1.5, color.gamma_multiply(0.4)),
+                    );
+                }
+            }
+
+            last_edge_screen = Some(edge_screen);
+        }
+
+        // Draw the end-of-line marker where the line ends (the middle of the last crossed
+        // edge), or at the player's tile center if it has not moved yet.
+        let marker_pos = last_edge_screen
+            .unwrap_or_else(|| transform.map_point(tile_center(game.players[player_index].tile)));
+        canvas.circle(marker_pos, transform.map_dist(0.25), color);
+    }
+}
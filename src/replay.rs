@@ -0,0 +1,77 @@
+//! Saving and loading matches as compact binary replays, using postcard.
+//!
+//! Rather than storing every full `GameState`, a replay stores only what's needed to
+//! deterministically reconstruct the match: the RNG seed, the initial board, and the
+//! per-turn moves. `frames()` replays those moves to rebuild the full `frames` vector.
+
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+
+use hex2d::Coordinate;
+use serde::{Deserialize, Serialize};
+
+use crate::game::GameState;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u32,
+    pub num_players: u8,
+    pub obstacles: Vec<Coordinate<i32>>,
+    /// The moves chosen for each player, one entry per turn.
+    pub moves: Vec<Vec<Option<Coordinate<i32>>>>,
+}
+
+impl Replay {
+    /// Records a replay of a match that started from the given seed/board and was advanced
+    /// by the given sequence of turns.
+    pub fn new(
+        seed: u32,
+        num_players: u8,
+        obstacles: HashSet<Coordinate<i32>>,
+        moves: Vec<Vec<Option<Coordinate<i32>>>>,
+    ) -> Self {
+        Self {
+            seed,
+            num_players,
+            obstacles: obstacles.into_iter().collect(),
+            moves,
+        }
+    }
+
+    /// Reconstructs the full frame history by replaying the recorded moves from a fresh
+    /// `GameState`.
+    pub fn frames(&self) -> Vec<GameState> {
+        let obstacles = self.obstacles.iter().copied().collect();
+        let mut frames = vec![GameState::new_with_obstacles(
+            self.seed,
+            self.num_players,
+            obstacles,
+        )];
+        for turn_moves in &self.moves {
+            let next = frames.last().unwrap().step_replay(turn_moves);
+            frames.push(next);
+        }
+        frames
+    }
+
+    /// Writes this replay as compact binary.
+    pub fn save_binary(&self, mut writer: impl Write) -> io::Result<()> {
+        let bytes = postcard::to_stdvec(self).map_err(io::Error::other)?;
+        writer.write_all(&bytes)
+    }
+
+    /// Reads a replay previously written by `save_binary`.
+    pub fn load_binary(bytes: &[u8]) -> io::Result<Self> {
+        postcard::from_bytes(bytes).map_err(io::Error::other)
+    }
+
+    /// Writes this replay as human-readable JSON, for inspecting or hand-editing a replay.
+    pub fn save_json(&self, writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(writer, self).map_err(io::Error::other)
+    }
+
+    /// Reads a replay previously written by `save_json`.
+    pub fn load_json(reader: impl Read) -> io::Result<Self> {
+        serde_json::from_reader(reader).map_err(io::Error::other)
+    }
+}
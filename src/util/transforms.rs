@@ -1,13 +1,60 @@
 //! Transforms between coordinate systems (such as grid/logical <=> screen pixels).
 
+use std::f32::consts::PI;
+
 use eframe::egui::Pos2;
 
-/// A 2D transform consisting of per-axis scale and translation.
+/// An angle, stored internally in radians.
+///
+/// Using a newtype instead of a bare `f32` avoids ambiguity between degrees and radians
+/// at call sites.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Angle(f32);
+
+#[allow(dead_code)]
+impl Angle {
+    /// Creates an `Angle` from a value in radians.
+    pub fn from_radians(radians: f32) -> Self {
+        Self(radians)
+    }
+
+    /// Creates an `Angle` from a value in degrees.
+    pub fn from_degrees(degrees: f32) -> Self {
+        Self(degrees.to_radians())
+    }
+
+    /// Returns the angle in radians.
+    pub fn radians(self) -> f32 {
+        self.0
+    }
+
+    /// Returns the angle in degrees.
+    pub fn degrees(self) -> f32 {
+        self.0.to_degrees()
+    }
+
+    /// Returns this angle wrapped into `(-π, π]`.
+    pub fn normalized(self) -> Self {
+        let mut a = self.0 % (2.0 * PI);
+        if a <= -PI {
+            a += 2.0 * PI;
+        } else if a > PI {
+            a -= 2.0 * PI;
+        }
+        Self(a)
+    }
+}
+
+/// A 2D affine transform: a linear part `[[a, b], [c, d]]` plus a translation `(e, f)`.
+///
+/// `map_point((x, y)) = (a*x + b*y + e, c*x + d*y + f)`.
 pub struct Transform {
-    scale_x: f32,
-    scale_y: f32,
-    offset_x: f32,
-    offset_y: f32,
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+    f: f32,
 }
 
 #[allow(dead_code)]
@@ -42,46 +89,81 @@ impl Transform {
         let dst_x_middle = (dst_p1.x + dst_p2.x) / 2.0;
         let offset_x = dst_x_middle - src_x_middle * scale_x;
         Self {
-            scale_x,
-            scale_y,
-            offset_x,
-            offset_y,
+            a: scale_x,
+            b: 0.0,
+            c: 0.0,
+            d: scale_y,
+            e: offset_x,
+            f: offset_y,
         }
     }
 
     /// Swaps the X and Y components of this `Transform`.
     pub fn transpose(&self) -> Self {
         Self {
-            scale_x: self.scale_y,
-            scale_y: self.scale_x,
-            offset_x: self.offset_y,
-            offset_y: self.offset_x,
+            a: self.d,
+            b: self.c,
+            c: self.b,
+            d: self.a,
+            e: self.f,
+            f: self.e,
+        }
+    }
+
+    /// Returns a new `Transform` that first applies this transform, then rotates the result by
+    /// `angle` about `pivot` (all in the destination/output space).
+    pub fn rotate(&self, angle: Angle, pivot: Pos2) -> Self {
+        let (sin, cos) = angle.radians().sin_cos();
+        // Rotation matrix `[[cos, -sin], [sin, cos]]` composed with this transform's linear part.
+        let a = cos * self.a - sin * self.c;
+        let b = cos * self.b - sin * self.d;
+        let c = sin * self.a + cos * self.c;
+        let d = sin * self.b + cos * self.d;
+        // Translation: rotate this transform's translation, then rotate about the pivot.
+        let rotated_e = cos * self.e - sin * self.f;
+        let rotated_f = sin * self.e + cos * self.f;
+        let pivot_e = pivot.x - (cos * pivot.x - sin * pivot.y);
+        let pivot_f = pivot.y - (sin * pivot.x + cos * pivot.y);
+        Self {
+            a,
+            b,
+            c,
+            d,
+            e: rotated_e + pivot_e,
+            f: rotated_f + pivot_f,
         }
     }
 
     /// Returns the inverse `Transform`.
     /// Panics if the transformation is not invertible.
     pub fn inverse(&self) -> Self {
-        assert!(self.scale_x != 0.0);
-        assert!(self.scale_y != 0.0);
+        let det = self.a * self.d - self.b * self.c;
+        assert!(det != 0.0);
+        let inv_a = self.d / det;
+        let inv_b = -self.b / det;
+        let inv_c = -self.c / det;
+        let inv_d = self.a / det;
         Self {
-            scale_x: self.scale_x.recip(),
-            scale_y: self.scale_y.recip(),
-            offset_x: -self.offset_x / self.scale_x,
-            offset_y: -self.offset_y / self.scale_y,
+            a: inv_a,
+            b: inv_b,
+            c: inv_c,
+            d: inv_d,
+            e: -(inv_a * self.e + inv_b * self.f),
+            f: -(inv_c * self.e + inv_d * self.f),
         }
     }
 
     /// Applies the transformation to a point.
     pub fn map_point(&self, p: Pos2) -> Pos2 {
         Pos2::new(
-            p.x * self.scale_x + self.offset_x,
-            p.y * self.scale_y + self.offset_y,
+            self.a * p.x + self.b * p.y + self.e,
+            self.c * p.x + self.d * p.y + self.f,
         )
     }
 
     /// Applies a scalar transformation
     pub fn map_dist(&self, x: f32) -> f32 {
-        (x * self.scale_x).abs() * x.signum()
+        let det = self.a * self.d - self.b * self.c;
+        x.abs() * det.abs().sqrt() * x.signum()
     }
 }
@@ -1,28 +1,62 @@
+mod editor;
+mod export;
 pub mod game;
+mod render;
+mod replay;
 mod util;
 
 use std::collections::HashSet;
 
 use eframe::egui;
 use eframe::egui::{Color32, Pos2, Rounding, Stroke};
+use editor::CurrentTool;
 use egui::epaint::QuadraticBezierShape;
 use egui::{pos2, vec2, Frame, Shape, Vec2};
-use game::GRID_RADIUS;
-use hex2d::{Coordinate, Direction, Spacing, Spin};
-use rand::seq::SliceRandom;
+use game::{GameState, GRID_RADIUS};
+use hex2d::{Coordinate, Direction, Spacing};
 use util::transforms::Transform;
 
+/// The RNG seed used for new games, chosen arbitrarily for a reproducible default match.
+const GAME_SEED: u32 = 0xC0FF_EE42;
+
+/// The number of players in a new game.
+const NUM_PLAYERS: u8 = 3;
+
+/// The color used to draw each player's path, indexed by player ID.
+pub(crate) const PLAYER_COLORS: [Color32; NUM_PLAYERS as usize] =
+    [Color32::RED, Color32::GREEN, Color32::YELLOW];
+
+/// The path a saved match replay is written to and read from.
+const SAVE_PATH: &str = "match.postcard";
+
+/// The path a human-readable JSON copy of a saved match replay is written to and read from.
+const SAVE_JSON_PATH: &str = "match.json";
+
+/// The path an exported GIF is written to.
+const EXPORT_GIF_PATH: &str = "match.gif";
+/// The pixel dimensions of an exported GIF.
+const EXPORT_WIDTH: u16 = 800;
+const EXPORT_HEIGHT: u16 = 600;
+/// The delay between frames of an exported GIF, in milliseconds.
+const EXPORT_FRAME_DELAY_MS: u16 = 100;
+
+/// Time constant (in seconds) for the exponential smoothing of the camera and zoom.
+const CAMERA_SMOOTHING_TAU: f32 = 0.12;
+
+/// Below this distance from the target, the camera/zoom are considered settled and stop animating.
+const CAMERA_SMOOTHING_EPSILON: f32 = 1.0e-4;
+
 const SQRT_3: f32 = 1.7320508;
 const GRID_WIDTH_IN_SIDE_LENGTHS: f32 = SQRT_3 * (GRID_RADIUS * 2 + 1) as f32;
 const GRID_HEIGHT_IN_SIDE_LENGTHS: f32 = 1.5 * (GRID_RADIUS * 2 + 1) as f32 + 0.5;
 
 // plus visual padding in the GUI:
-const GRID_WIDTH: f32 = GRID_WIDTH_IN_SIDE_LENGTHS + 2.0;
-const GRID_HEIGHT: f32 = GRID_HEIGHT_IN_SIDE_LENGTHS + 2.0;
+pub(crate) const GRID_WIDTH: f32 = GRID_WIDTH_IN_SIDE_LENGTHS + 2.0;
+pub(crate) const GRID_HEIGHT: f32 = GRID_HEIGHT_IN_SIDE_LENGTHS + 2.0;
 
 /// The corners of a hexagon with side length 1 that is centered at the origin.
 /// The first corner is repeated at the end.
-const HEXAGON_CORNERS: [Vec2; 7] = [
+pub(crate) const HEXAGON_CORNERS: [Vec2; 7] = [
     vec2(0.0, 1.0),
     vec2(SQRT_3 / 2.0, 0.5),
     vec2(SQRT_3 / 2.0, -0.5),
@@ -32,6 +66,25 @@ const HEXAGON_CORNERS: [Vec2; 7] = [
     vec2(0.0, 1.0),
 ];
 
+/// The center of a hex tile, in world coordinates.
+pub(crate) fn tile_center(tile: Coordinate) -> Pos2 {
+    tile.to_pixel(Spacing::PointyTop(1.0)).into()
+}
+
+/// The corners (in world coordinates) of the edge shared by `tile` and `neighbor`.
+pub(crate) fn edge_corners(tile: Coordinate, neighbor: Coordinate) -> [Pos2; 2] {
+    let index = match tile.direction_to_cw(neighbor).unwrap() {
+        Direction::ZY => 0,
+        Direction::XY => 1,
+        Direction::XZ => 2,
+        Direction::YZ => 3,
+        Direction::YX => 4,
+        Direction::ZX => 5,
+    };
+    let center = tile_center(tile);
+    [center + HEXAGON_CORNERS[index], center + HEXAGON_CORNERS[index + 1]]
+}
+
 fn main() {
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
@@ -43,25 +96,51 @@ fn main() {
 }
 
 struct GridGameViewer {
-    frames: Vec<()>,
+    frames: Vec<GameState>,
     current_frame: usize,
     pointer_pos: String,
     zoom: f32,
+    zoom_target: f32,
     camera: Pos2,
+    camera_target: Pos2,
     draw_lines: bool,
     highlight_edges: bool,
+    editor_mode: bool,
+    current_tool: CurrentTool,
+    /// The obstacle tiles authored in the board editor; applied on the next "Reset Game".
+    board: HashSet<Coordinate<i32>>,
+    /// The first corner clicked while using the `Rectangle` tool, if any.
+    rect_start: Option<Coordinate<i32>>,
+    /// The last tile toggled by the `Brush` tool during the current drag, to avoid
+    /// re-toggling it every frame the pointer stays over it.
+    last_brushed_tile: Option<Coordinate<i32>>,
+
+    /// The RNG seed of the current match, recorded so it can be saved as a replay.
+    game_seed: u32,
+    /// The moves chosen for each player on each turn so far, recorded so the match can be
+    /// saved as a replay.
+    moves_history: Vec<Vec<Option<Coordinate<i32>>>>,
 }
 
 impl Default for GridGameViewer {
     fn default() -> Self {
         Self {
-            frames: vec![()],
+            frames: vec![GameState::new(GAME_SEED, NUM_PLAYERS)],
             current_frame: 0,
             pointer_pos: "".to_string(),
             zoom: 1.0,
+            zoom_target: 1.0,
             camera: pos2(0.0, 0.0),
+            camera_target: pos2(0.0, 0.0),
             draw_lines: true,
             highlight_edges: true,
+            editor_mode: false,
+            current_tool: CurrentTool::default(),
+            board: HashSet::new(),
+            rect_start: None,
+            last_brushed_tile: None,
+            game_seed: GAME_SEED,
+            moves_history: Vec::new(),
         }
     }
 }
@@ -91,146 +170,168 @@ impl GridGameViewer {
     fn paint_game(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
         let origin = Coordinate::new(0, 0);
 
-        ctx.input(|i| {
-            self.zoom *= (i.scroll_delta.y / 500.0).exp();
-            self.zoom = self.zoom.clamp(1.0, 1.0e4);
-            if i.pointer.is_decidedly_dragging() {
+        let dt = ctx.input(|i| {
+            self.zoom_target *= (i.scroll_delta.y / 500.0).exp();
+            self.zoom_target = self.zoom_target.clamp(1.0, 1.0e4);
+            let can_pan = !self.editor_mode || self.current_tool == CurrentTool::Move;
+            if can_pan && i.pointer.is_decidedly_dragging() {
                 let px_scale = self.make_transform(ui).map_dist(1.0);
-                self.camera.x -= i.pointer.delta().x / px_scale;
-                self.camera.y += i.pointer.delta().y / px_scale;
-                self.camera = self.camera.clamp(
+                self.camera_target.x -= i.pointer.delta().x / px_scale;
+                self.camera_target.y += i.pointer.delta().y / px_scale;
+                self.camera_target = self.camera_target.clamp(
                     pos2(-GRID_WIDTH / 2.0, -GRID_HEIGHT / 2.0),
                     pos2(GRID_WIDTH / 2.0, GRID_HEIGHT / 2.0),
                 );
             }
+            i.stable_dt
         });
 
+        // Ease the current camera/zoom toward their targets, frame-rate independently.
+        let smoothing = 1.0 - (-dt / CAMERA_SMOOTHING_TAU).exp();
+        self.zoom += (self.zoom_target - self.zoom) * smoothing;
+        self.camera += (self.camera_target - self.camera) * smoothing;
+        if (self.zoom_target - self.zoom).abs() > CAMERA_SMOOTHING_EPSILON
+            || (self.camera_target - self.camera).length() > CAMERA_SMOOTHING_EPSILON
+        {
+            ctx.request_repaint();
+        }
+
         let ui_rect = ui.max_rect();
         let world_to_screen = self.make_transform(ui);
         let painter = ui.painter_at(ui_rect);
 
-        self.pointer_pos = match ctx.pointer_latest_pos() {
+        let hovered_tile = ctx.pointer_latest_pos().map(|pos| {
+            let world_pos = world_to_screen.inverse().map_point(pos);
+            (
+                world_pos,
+                Coordinate::from_pixel(world_pos.x, world_pos.y, Spacing::PointyTop(1.0)),
+            )
+        });
+
+        self.pointer_pos = match hovered_tile {
             None => "".to_string(),
-            Some(pos) => {
-                let pos = world_to_screen.inverse().map_point(pos);
-                let tile: Coordinate<i32> =
-                    Coordinate::from_pixel(pos.x, pos.y, Spacing::PointyTop(1.0));
-                format!(
-                    "({:.1}, {:.1}) Hexagon: (x={}, y={}, z={}, r={})",
-                    pos.x,
-                    pos.y,
-                    tile.x,
-                    tile.y,
-                    tile.z(),
-                    tile.distance(origin),
-                )
-            }
+            Some((pos, tile)) => format!(
+                "({:.1}, {:.1}) Hexagon: (x={}, y={}, z={}, r={})",
+                pos.x,
+                pos.y,
+                tile.x,
+                tile.y,
+                tile.z(),
+                tile.distance(origin),
+            ),
         };
 
-        // background
-        painter.rect(
-            ui_rect,
-            Rounding::ZERO,
-            Color32::from_gray(10),
-            Stroke::NONE,
-        );
-
-        // let game = self.frames[self.current_frame];
+        if self.editor_mode {
+            if let Some((_, tile)) = hovered_tile {
+                self.handle_editor_input(ctx, tile);
+            }
+        }
 
-        let get_hex_center_corners = |tile: Coordinate| {
-            let tile_center: Pos2 = tile.to_pixel(Spacing::PointyTop(1.0)).into();
-            (
-                tile_center,
-                HEXAGON_CORNERS.map(|p| world_to_screen.map_point(tile_center + p)),
-            )
-        };
+        let mut canvas = EguiCanvas { painter: &painter };
+        render::render_frame(
+            &self.frames,
+            self.current_frame,
+            &world_to_screen,
+            &mut canvas,
+            self.draw_lines,
+            self.highlight_edges,
+        );
 
-        // TODO draw game
-        for r in 0..=GRID_RADIUS {
-            let ring = origin.ring_iter(r as i32, Spin::CW(Direction::XY));
-
-            for tile in ring {
-                let (_, tile_corners) = get_hex_center_corners(tile);
-                painter.add(Shape::convex_polygon(
-                    tile_corners.to_vec(),
-                    match r {
-                        0 => Color32::from_rgba_unmultiplied(255, 128, 0, 15),
-                        GRID_RADIUS => Color32::WHITE.gamma_multiply(0.05),
-                        _ => Color32::TRANSPARENT,
-                    },
+        if self.editor_mode {
+            for &tile in &self.board {
+                let corners =
+                    HEXAGON_CORNERS.map(|p| world_to_screen.map_point(tile_center(tile) + p));
+                canvas.polygon(
+                    &corners,
+                    Color32::from_rgba_unmultiplied(200, 50, 50, 120),
                     Stroke::new(0.5, Color32::from_gray(50)),
-                ));
+                );
             }
         }
+    }
 
-        let mut occupied_edges = HashSet::new();
-        for color in [Color32::RED, Color32::GREEN, Color32::YELLOW] {
-            let mut last_tile = Coordinate::new(10, -3);
-            let mut last_edge = None;
-            for _ in 0..100 {
-                // Get the next tile in the path.
-                let tile = *last_tile
-                    .neighbors()
-                    .choose(&mut rand::thread_rng())
-                    .unwrap();
-
-                let tile_center = |tile: Coordinate| tile.to_pixel(Spacing::PointyTop(1.0)).into();
-                let edge_endpoints = |edge_index| {
-                    let e1 = tile_center(tile) + HEXAGON_CORNERS[edge_index];
-                    let e2 = tile_center(tile) + HEXAGON_CORNERS[edge_index + 1];
-                    [e1, e2].map(|e| world_to_screen.map_point(e))
-                };
-
-                let [e1, e2] = edge_endpoints(match tile.direction_to_cw(last_tile).unwrap() {
-                    Direction::ZY => 0,
-                    Direction::XY => 1,
-                    Direction::XZ => 2,
-                    Direction::YZ => 3,
-                    Direction::YX => 4,
-                    Direction::ZX => 5,
-                });
-                let edge = e1 + (e2 - e1) / 2.0;
-                if last_edge == Some(edge)
-                    || !occupied_edges.insert((tile.min(last_tile), tile.max(last_tile)))
-                {
-                    // for debug, to prevent illegal random moves
-                    continue;
-                }
-                assert_ne!(last_edge, Some(edge)); // Verify that this isn't a 180deg turn.
+    /// Applies the active editor tool's effect on `tile` based on the current pointer state.
+    fn handle_editor_input(&mut self, ctx: &egui::Context, tile: Coordinate<i32>) {
+        let origin = Coordinate::new(0, 0);
+        if tile.distance(origin) > GRID_RADIUS as i32 {
+            return;
+        }
+        let (clicked, down) =
+            ctx.input(|i| (i.pointer.primary_clicked(), i.pointer.primary_down()));
 
-                if self.highlight_edges {
-                    // Redraw the hexagon edge to show that it is now off-limits.
-                    painter.line_segment([e1, e2], Stroke::new(1.0, Color32::WHITE));
+        match self.current_tool {
+            CurrentTool::Move => {}
+            CurrentTool::Brush => {
+                if down {
+                    if self.last_brushed_tile != Some(tile) {
+                        if !self.board.insert(tile) {
+                            self.board.remove(&tile);
+                        }
+                        self.last_brushed_tile = Some(tile);
+                    }
+                } else {
+                    self.last_brushed_tile = None;
                 }
-
-                if self.draw_lines {
-                    // Draw the curved segment of the player's line.
-                    if let Some(last_edge) = last_edge {
-                        painter.add(egui::Shape::QuadraticBezier(QuadraticBezierShape {
-                            points: [
-                                last_edge,
-                                world_to_screen.map_point(tile_center(last_tile)),
-                                edge,
-                            ],
-                            closed: false,
-                            fill: Color32::TRANSPARENT,
-                            stroke: Stroke::new(1.5, color.gamma_multiply(0.4)),
-                        }));
+            }
+            CurrentTool::Fill => {
+                if clicked {
+                    let adding = !self.board.contains(&tile);
+                    for t in editor::flood_fill(&self.board, tile) {
+                        if adding {
+                            self.board.insert(t);
+                        } else {
+                            self.board.remove(&t);
+                        }
                     }
                 }
-
-                last_tile = tile;
-                last_edge = Some(edge);
             }
-
-            // Draw the end-of-line marker.
-            if let Some(last_edge) = last_edge {
-                painter.circle_filled(last_edge, world_to_screen.map_dist(0.25), color);
+            CurrentTool::Rectangle => {
+                if clicked {
+                    if let Some(start) = self.rect_start.take() {
+                        self.board.extend(editor::rect_tiles(start, tile));
+                    } else {
+                        self.rect_start = Some(tile);
+                    }
+                }
             }
         }
     }
 }
 
+/// A [`render::Canvas`] that draws onto a live egui [`egui::Painter`].
+struct EguiCanvas<'a> {
+    painter: &'a egui::Painter,
+}
+
+impl render::Canvas for EguiCanvas<'_> {
+    fn fill_background(&mut self, color: Color32) {
+        self.painter
+            .rect(self.painter.clip_rect(), Rounding::ZERO, color, Stroke::NONE);
+    }
+
+    fn polygon(&mut self, points: &[Pos2], fill: Color32, stroke: Stroke) {
+        self.painter
+            .add(Shape::convex_polygon(points.to_vec(), fill, stroke));
+    }
+
+    fn line(&mut self, points: [Pos2; 2], stroke: Stroke) {
+        self.painter.line_segment(points, stroke);
+    }
+
+    fn quadratic_bezier(&mut self, points: [Pos2; 3], stroke: Stroke) {
+        self.painter.add(Shape::QuadraticBezier(QuadraticBezierShape {
+            points,
+            closed: false,
+            fill: Color32::TRANSPARENT,
+            stroke,
+        }));
+    }
+
+    fn circle(&mut self, center: Pos2, radius: f32, fill: Color32) {
+        self.painter.circle_filled(center, radius, fill);
+    }
+}
+
 impl eframe::App for GridGameViewer {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::TopBottomPanel::top("menu").show(ctx, |ui| {
@@ -250,38 +351,130 @@ impl eframe::App for GridGameViewer {
                         self.current_frame -= 1;
                     }
                     if ui.button("Reset Game").clicked() {
-                        self.frames = vec![()];
+                        self.game_seed = GAME_SEED;
+                        self.frames = vec![GameState::new_with_obstacles(
+                            self.game_seed,
+                            NUM_PLAYERS,
+                            self.board.clone(),
+                        )];
+                        self.moves_history.clear();
                         self.current_frame = 0;
                     }
+                    if ui.button("Save").clicked() {
+                        let replay = replay::Replay::new(
+                            self.game_seed,
+                            self.frames[0].players.len() as u8,
+                            self.frames[0].obstacles.clone(),
+                            self.moves_history.clone(),
+                        );
+                        match std::fs::File::create(SAVE_PATH)
+                            .and_then(|file| replay.save_binary(file))
+                        {
+                            Ok(()) => println!("Saved match to {SAVE_PATH}"),
+                            Err(err) => eprintln!("Failed to save match: {err}"),
+                        }
+                    }
+                    if ui.button("Open").clicked() {
+                        match std::fs::read(SAVE_PATH)
+                            .and_then(|bytes| replay::Replay::load_binary(&bytes))
+                        {
+                            Ok(replay) => {
+                                self.game_seed = replay.seed;
+                                self.board = replay.obstacles.iter().copied().collect();
+                                self.moves_history = replay.moves.clone();
+                                self.frames = replay.frames();
+                                self.current_frame = 0;
+                            }
+                            Err(err) => eprintln!("Failed to open match: {err}"),
+                        }
+                    }
+                    if ui
+                        .button("Save JSON")
+                        .on_hover_text("Write a human-readable copy for inspecting or hand-editing a replay")
+                        .clicked()
+                    {
+                        let replay = replay::Replay::new(
+                            self.game_seed,
+                            self.frames[0].players.len() as u8,
+                            self.frames[0].obstacles.clone(),
+                            self.moves_history.clone(),
+                        );
+                        match std::fs::File::create(SAVE_JSON_PATH)
+                            .and_then(|file| replay.save_json(file))
+                        {
+                            Ok(()) => println!("Saved match to {SAVE_JSON_PATH}"),
+                            Err(err) => eprintln!("Failed to save match: {err}"),
+                        }
+                    }
+                    if ui.button("Open JSON").clicked() {
+                        match std::fs::File::open(SAVE_JSON_PATH)
+                            .and_then(|file| replay::Replay::load_json(file))
+                        {
+                            Ok(replay) => {
+                                self.game_seed = replay.seed;
+                                self.board = replay.obstacles.iter().copied().collect();
+                                self.moves_history = replay.moves.clone();
+                                self.frames = replay.frames();
+                                self.current_frame = 0;
+                            }
+                            Err(err) => eprintln!("Failed to open match: {err}"),
+                        }
+                    }
+                    if ui.button("Export GIF").clicked() {
+                        match std::fs::File::create(EXPORT_GIF_PATH).and_then(|file| {
+                            export::export_gif(
+                                &self.frames,
+                                EXPORT_WIDTH,
+                                EXPORT_HEIGHT,
+                                EXPORT_FRAME_DELAY_MS,
+                                self.draw_lines,
+                                self.highlight_edges,
+                                file,
+                            )
+                        }) {
+                            Ok(()) => println!("Exported match to {EXPORT_GIF_PATH}"),
+                            Err(err) => eprintln!("Failed to export GIF: {err}"),
+                        }
+                    }
+                    let at_last_frame = self.current_frame == self.frames.len() - 1;
+                    let game_over = self.frames.last().unwrap().is_over();
                     if ui
-                        .add_enabled(
-                            self.current_frame != self.frames.len() - 1,
-                            // || self.frames[self.current_frame].game_winner().is_none(),
-                            egui::Button::new(">"),
-                        )
+                        .add_enabled(!at_last_frame || !game_over, egui::Button::new(">"))
                         .clicked()
                     {
-                        if self.current_frame == self.frames.len() - 1 {
-                            // let game = self.frames[self.current_frame];
-                            // TODO
-                            // self.frames.push(game);
+                        if at_last_frame {
+                            let (next, moves) = self.frames[self.current_frame].step_random();
+                            self.frames.push(next);
+                            self.moves_history.push(moves);
                         }
                         self.current_frame += 1;
                     }
                     if ui
-                        .add_enabled(
-                            self.current_frame != self.frames.len() - 1,
-                            egui::Button::new(">>"),
-                        )
+                        .add_enabled(!at_last_frame || !game_over, egui::Button::new(">>"))
                         .clicked()
                     {
-                        todo!();
+                        while !self.frames.last().unwrap().is_over() {
+                            let (next, moves) = self.frames.last().unwrap().step_random();
+                            self.frames.push(next);
+                            self.moves_history.push(moves);
+                        }
+                        self.current_frame = self.frames.len() - 1;
                     }
 
                     ui.separator();
 
                     ui.checkbox(&mut self.draw_lines, "Draw lines");
                     ui.checkbox(&mut self.highlight_edges, "Highlight edges");
+
+                    ui.separator();
+
+                    ui.checkbox(&mut self.editor_mode, "Editor");
+                    if self.editor_mode {
+                        ui.radio_value(&mut self.current_tool, CurrentTool::Move, "Move");
+                        ui.radio_value(&mut self.current_tool, CurrentTool::Brush, "Brush");
+                        ui.radio_value(&mut self.current_tool, CurrentTool::Fill, "Fill");
+                        ui.radio_value(&mut self.current_tool, CurrentTool::Rectangle, "Rectangle");
+                    }
                 });
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.label(&self.pointer_pos);